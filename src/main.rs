@@ -1,59 +1,127 @@
-struct Position(usize);
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// A bitboard of the N×N grid points: bit `point` is set when that point
+/// has been swiped. Shared by the counting DP (`Position::swiped`) and the
+/// pattern enumerator so there is a single tested place for the bit math.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+struct SquareSet<const N: usize>(u64);
+
+impl<const N: usize> SquareSet<N> {
+    pub const fn num_points() -> usize {
+        N * N
+    }
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn is_set(&self, point: usize) -> bool {
+        assert!(point < Self::num_points());
+        (self.0 >> point) & 1 != 0
+    }
 
-impl From<usize> for Position {
+    pub fn with(&self, point: usize) -> Self {
+        assert!(point < Self::num_points());
+        *self | Self(1 << point)
+    }
+
+    pub fn is_subset_of(&self, other: Self) -> bool {
+        (*self & other) == *self
+    }
+}
+
+impl<const N: usize> std::ops::BitAnd for SquareSet<N> {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl<const N: usize> std::ops::BitOr for SquareSet<N> {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl<const N: usize> std::ops::Not for SquareSet<N> {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        let all_points = (1u64 << Self::num_points()) - 1;
+        Self(!self.0 & all_points)
+    }
+}
+
+struct Position<const N: usize>(usize);
+
+impl<const N: usize> From<usize> for Position<N> {
     fn from(index: usize) -> Self {
         assert!(index < Self::all_positions_count());
         Self(index)
     }
 }
 
-impl Position {
+impl<const N: usize> Position<N> {
+    pub const fn num_points() -> usize {
+        N * N
+    }
+
     pub const fn all_positions_count() -> usize {
-        // The indexes in the array are `last_swiped + 9 * index`
+        // The indexes in the array are `last_swiped + num_points() * index`
         //
-        // `last_swiped` is the position (0..9) of the last swiped point and
+        // `last_swiped` is the position (0..num_points()) of the last swiped point and
         // `index` is the sum of (1<<point) for every swiped point.
         //
         // For example, if you swap the point 1, 2 and 5, the index will be
-        // 5 (last swapped) + 9*(1<<1) + 9*(1<<2) + 9*(1<<5)
+        // 5 (last swapped) + num_points()*(1<<1) + num_points()*(1<<2) + num_points()*(1<<5)
         //
         // NB: There is a +1 because of the empty board
-        9 * (1 + (1 << 9))        
+        Self::num_points() * (1 + (1 << Self::num_points()))
     }
 
     pub fn base(&self) -> usize {
-        self.0 / 9
+        self.0 / Self::num_points()
     }
 
     pub fn last_swiped(&self) -> usize {
-        self.0 % 9
+        self.0 % Self::num_points()
     }
 
     fn index_of_point(point: usize) -> usize {
-        9 /* last_swiped */ * (1 << point) /* 1,2,4,8,… */
+        Self::num_points() /* last_swiped */ * (1 << point) /* 1,2,4,8,… */
     }
 
     pub fn from_point(point: usize) -> Self {
-        assert!(point < 9);
-        
+        assert!(point < Self::num_points());
+
         Self(Self::index_of_point(point) + point)
     }
 
+    // the set of points swiped so far, as a bitboard
+    pub fn swiped(&self) -> SquareSet<N> {
+        SquareSet(self.base() as u64)
+    }
+
     pub fn is_swiped(&self, point: usize) -> bool {
-        assert!(point < 9);
-        
-        // if the bit at the position of `point` is set, then it was already swiped
-        (self.base() & (1<<point)) != 0
+        assert!(point < Self::num_points());
+
+        self.swiped().is_set(point)
+    }
+
+    // row/column of a point index, on the N×N grid (point = row*N + col)
+    fn to_row_col(point: usize) -> (usize, usize) {
+        (point / N, point % N)
     }
 
     pub fn swipe_to(&self, next_swiped_point: usize) -> Option<Self> {
-        assert!(next_swiped_point < 9);
+        assert!(next_swiped_point < Self::num_points());
 
-        // NB: The keypad looks like:
-        // 0 1 2
-        // 3 4 5
-        // 6 7 8
-        
         // To be allowed to swipe …
         //
         // _ the new point must not have have already been swiped
@@ -62,31 +130,29 @@ impl Position {
         }
 
         // _ the point must not go throught an unswiped point
-        match (self.last_swiped(), next_swiped_point) {
-            // corners
-            (0, 2) if !self.is_swiped(1) => return None,
-            (0, 8) if !self.is_swiped(4) => return None,
-            (0, 6) if !self.is_swiped(3) => return None,
-            (2, 0) if !self.is_swiped(1) => return None,
-            (2, 6) if !self.is_swiped(4) => return None,
-            (2, 8) if !self.is_swiped(5) => return None,
-            (6, 0) if !self.is_swiped(3) => return None,
-            (6, 2) if !self.is_swiped(4) => return None,
-            (6, 8) if !self.is_swiped(7) => return None,
-            (8, 6) if !self.is_swiped(7) => return None,
-            (8, 0) if !self.is_swiped(4) => return None,
-            (8, 2) if !self.is_swiped(5) => return None,
-            // border
-            (1, 7) if !self.is_swiped(4) => return None,
-            (7, 1) if !self.is_swiped(4) => return None,
-            (3, 5) if !self.is_swiped(4) => return None,
-            (5, 3) if !self.is_swiped(4) => return None,
-            // all combinations are valid from the center of the keypad
-            // all other combination are valid
-            _ => (),
-        }
-
-        Some(Self((self.base() * 9) + Self::index_of_point(next_swiped_point) + next_swiped_point /*last_swiped*/))
+        //
+        // A move from a=(r0,c0) to b=(r1,c1) crosses every intermediate
+        // lattice point on the segment a-b: with dr=r1-r0, dc=c1-c0 and
+        // g=gcd(|dr|,|dc|), those points are (r0 + k*dr/g, c0 + k*dc/g)
+        // for k=1..g-1. Each of them must already be swiped.
+        let (r0, c0) = Self::to_row_col(self.last_swiped());
+        let (r1, c1) = Self::to_row_col(next_swiped_point);
+        let dr = r1 as isize - r0 as isize;
+        let dc = c1 as isize - c0 as isize;
+        let g = gcd(dr.unsigned_abs(), dc.unsigned_abs()) as isize;
+
+        for k in 1..g {
+            let r = (r0 as isize + k * dr / g) as usize;
+            let c = (c0 as isize + k * dc / g) as usize;
+            let intermediate = r * N + c;
+            if !self.is_swiped(intermediate) {
+                return None;
+            }
+        }
+
+        Some(Self(
+            (self.base() * Self::num_points()) + Self::index_of_point(next_swiped_point) + next_swiped_point, /*last_swiped*/
+        ))
     }
 
     pub fn swiped_points(&self) -> u32 {
@@ -94,45 +160,128 @@ impl Position {
     }
 }
 
-struct States([u32 /* possibilities to hit this state */; Position::all_positions_count()]);
+// DFS over `swipe_to`, collecting every legal swipe sequence of exactly
+// `len` points reachable from `position`. `visited` tracks the same
+// swiped points as `position`'s own bitboard, kept alongside it as the
+// shared `SquareSet` abstraction rather than re-deriving it from
+// `position` on every call.
+fn enumerate_from<const N: usize>(
+    position: Position<N>,
+    visited: SquareSet<N>,
+    path: &mut Vec<usize>,
+    len: usize,
+    patterns: &mut Vec<Vec<usize>>,
+) {
+    debug_assert!(visited.is_subset_of(position.swiped()) && position.swiped().is_subset_of(visited));
+
+    if path.len() == len {
+        patterns.push(path.clone());
+        return;
+    }
+
+    // only points not yet swiped are worth trying; `swipe_to` re-checks
+    // the remaining legality rules (skip-over) on top of this
+    let candidates = !visited;
+    for next_swiped_point in 0..Position::<N>::num_points() {
+        if !candidates.is_set(next_swiped_point) {
+            continue;
+        }
+        if let Some(next_position) = position.swipe_to(next_swiped_point) {
+            path.push(next_swiped_point);
+            enumerate_from(next_position, visited.with(next_swiped_point), path, len, patterns);
+            path.pop();
+        }
+    }
+}
+
+/// All legal patterns of exactly `len` points, as the sequence of point
+/// indices in swipe order.
+pub fn patterns_of_length<const N: usize>(len: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut patterns = Vec::new();
+
+    for start in 0..Position::<N>::num_points() {
+        let mut path = vec![start];
+        let visited = SquareSet::<N>::empty().with(start);
+        enumerate_from(Position::<N>::from_point(start), visited, &mut path, len, &mut patterns);
+    }
+
+    patterns.into_iter()
+}
+
+/// A concrete swipe sequence, with the ability to render itself on the
+/// N×N grid.
+struct Pattern<const N: usize>(Vec<usize>);
+
+impl<const N: usize> From<Vec<usize>> for Pattern<N> {
+    fn from(swipes: Vec<usize>) -> Self {
+        Self(swipes)
+    }
+}
+
+impl<const N: usize> Pattern<N> {
+    // draws the pattern as an N×N grid, numbering each point in swipe
+    // order; points that were not swiped are drawn as `.`
+    pub fn draw(&self) -> String {
+        let mut swipe_order = vec![None; Position::<N>::num_points()];
+        for (order, &point) in self.0.iter().enumerate() {
+            swipe_order[point] = Some(order + 1);
+        }
+
+        let mut board = String::new();
+        for row in 0..N {
+            for col in 0..N {
+                if col > 0 {
+                    board.push(' ');
+                }
+                match swipe_order[row * N + col] {
+                    Some(order) => board.push_str(&format!("{order:2}")),
+                    None => board.push_str(" ."),
+                }
+            }
+            board.push('\n');
+        }
+
+        board
+    }
+}
 
-impl std::ops::Index<Position> for States {
+struct States<const N: usize>(Vec<u32> /* possibilities to hit this state */);
+
+impl<const N: usize> std::ops::Index<Position<N>> for States<N> {
     type Output = u32;
-    fn index(&self, position: Position) -> &Self::Output {
-        &self.0[position.0]        
+    fn index(&self, position: Position<N>) -> &Self::Output {
+        &self.0[position.0]
     }
 }
 
-impl std::ops::IndexMut<Position> for States {
-    fn index_mut(&mut self, position: Position) -> &mut Self::Output {
-        &mut self.0[position.0]        
+impl<const N: usize> std::ops::IndexMut<Position<N>> for States<N> {
+    fn index_mut(&mut self, position: Position<N>) -> &mut Self::Output {
+        &mut self.0[position.0]
     }
 }
 
-impl Default for States {
+impl<const N: usize> Default for States<N> {
     fn default() -> Self {
-        Self (
-            [0; Position::all_positions_count()],
-        )
+        Self(vec![0; Position::<N>::all_positions_count()])
     }
 }
 
-impl States {
+impl<const N: usize> States<N> {
     fn iter(&self) -> std::slice::Iter<'_, u32> {
         self.0.iter()
     }
 }
 
 #[derive(Default)]
-struct Step {
-    possibilities: States,
+struct Step<const N: usize> {
+    possibilities: States<N>,
 }
 
-impl Step {
+impl<const N: usize> Step<N> {
     pub fn init() -> Self {
         let mut current_step = Self::default();
 
-        for i in 0..9 {
+        for i in 0..Position::<N>::num_points() {
             current_step.possibilities[Position::from_point(i)] = 1;
         }
 
@@ -145,7 +294,7 @@ impl Step {
 
     pub fn validate(&self, step: u32) {
         for (index, &count) in self.possibilities.iter().enumerate() {
-            let position: Position = index.into();
+            let position: Position<N> = index.into();
             assert!(count == 0 || position.swiped_points() == step);
         }
     }
@@ -153,11 +302,11 @@ impl Step {
     pub fn next_step(&self) -> Self {
         let mut next = Self::default();
         for (index, &count) in self.possibilities.iter().enumerate() {
-            let current_position: Position = index.into();
+            let current_position: Position<N> = index.into();
             if count == 0 {
                 continue; // this point is not yet swiped
             }
-            for next_swiped_point in 0..9 {
+            for next_swiped_point in 0..Position::<N>::num_points() {
                 if let Some(next_position) = current_position.swipe_to(next_swiped_point) {
                     next.possibilities[next_position] += count;
                 }
@@ -165,6 +314,214 @@ impl Step {
         }
         next
     }
+
+    // Among the partial patterns reachable at this step, how many have no
+    // legal continuation at all: a real lock screen would auto-yield such
+    // a pattern because the finger can't go anywhere else.
+    pub fn stuck_possibilities(&self) -> u32 {
+        let mut stuck = 0;
+        for (index, &count) in self.possibilities.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let current_position: Position<N> = index.into();
+            let has_continuation = (0..Position::<N>::num_points())
+                .any(|next_swiped_point| current_position.swipe_to(next_swiped_point).is_some());
+            if !has_continuation {
+                stuck += count;
+            }
+        }
+        stuck
+    }
+}
+
+/// The range of pattern lengths a lock screen accepts, e.g. Android's
+/// real policy of 4 to 9 points.
+pub struct PatternPolicy {
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+impl PatternPolicy {
+    pub fn new(min_len: usize, max_len: usize) -> Self {
+        assert!(min_len >= 1);
+        assert!(min_len <= max_len);
+        Self { min_len, max_len }
+    }
+
+    pub fn android() -> Self {
+        Self::new(4, 9)
+    }
+}
+
+/// Total number of patterns whose length falls within `policy`, e.g. the
+/// "≥4 points" keyspace as a single call instead of a manual per-step sum.
+pub fn count_patterns<const N: usize>(policy: &PatternPolicy) -> u32 {
+    let mut current = Step::<N>::init();
+    let mut total = 0;
+
+    if policy.min_len <= 1 {
+        total += current.current_possibilities();
+    }
+
+    for step in 2..=policy.max_len as u32 {
+        current = current.next_step();
+        current.validate(step);
+        if step as usize >= policy.min_len {
+            total += current.current_possibilities();
+        }
+    }
+
+    total
+}
+
+/// For every length below `policy.min_len`, how many partial patterns get
+/// stuck (no legal continuation) before ever reaching the required
+/// minimum.
+pub fn stuck_prefixes_report<const N: usize>(policy: &PatternPolicy) -> Vec<(usize, u32)> {
+    let mut current = Step::<N>::init();
+    let mut report = Vec::new();
+
+    if policy.min_len > 1 {
+        report.push((1, current.stuck_possibilities()));
+    }
+
+    for step in 2..policy.min_len as u32 {
+        current = current.next_step();
+        current.validate(step);
+        report.push((step as usize, current.stuck_possibilities()));
+    }
+
+    report
+}
+
+/// One of the 8 symmetries of the square grid (the dihedral group D4:
+/// identity, 90/180/270° rotations, and 4 reflections), represented as a
+/// permutation of point indices.
+struct Symmetry(Vec<usize>);
+
+impl Symmetry {
+    fn apply_pattern(&self, pattern: &[usize]) -> Vec<usize> {
+        pattern.iter().map(|&point| self.0[point]).collect()
+    }
+}
+
+// Every symmetry of an N×N grid maps (row, col) to some (row', col');
+// since D4 preserves collinearity, it maps the legal-move graph to
+// itself, so a transformed valid pattern is always valid.
+type Transform = fn(usize, usize) -> (usize, usize);
+
+fn d4_symmetries<const N: usize>() -> Vec<Symmetry> {
+    let transforms: [Transform; 8] = [
+        |r, c| (r, c),                 // identity
+        |r, c| (c, N - 1 - r),         // rotate 90°
+        |r, c| (N - 1 - r, N - 1 - c), // rotate 180°
+        |r, c| (N - 1 - c, r),         // rotate 270°
+        |r, c| (r, N - 1 - c),         // reflect across the vertical axis
+        |r, c| (N - 1 - r, c),         // reflect across the horizontal axis
+        |r, c| (c, r),                 // reflect across the main diagonal
+        |r, c| (N - 1 - c, N - 1 - r), // reflect across the anti-diagonal
+    ];
+
+    transforms
+        .into_iter()
+        .map(|transform| {
+            let permutation = (0..Position::<N>::num_points())
+                .map(|point| {
+                    let (row, col) = (point / N, point % N);
+                    let (new_row, new_col) = transform(row, col);
+                    new_row * N + new_col
+                })
+                .collect();
+            Symmetry(permutation)
+        })
+        .collect()
+}
+
+/// Number of geometrically distinct patterns of `len` points, counting
+/// two patterns as the same if one is a rotation/reflection of the
+/// other. By Burnside's lemma the number of orbits under a group `G` is
+/// `(1/|G|) * Σ_g Fix(g)`, where `Fix(g)` is the number of valid
+/// patterns a symmetry `g` maps back onto themselves.
+pub fn distinct_patterns_of_length<const N: usize>(len: usize) -> u32 {
+    let patterns: Vec<Vec<usize>> = patterns_of_length::<N>(len).collect();
+    let group = d4_symmetries::<N>();
+
+    let fixed_sum: usize = group
+        .iter()
+        .map(|symmetry| {
+            patterns
+                .iter()
+                .filter(|pattern| &symmetry.apply_pattern(pattern) == *pattern)
+                .count()
+        })
+        .sum();
+
+    (fixed_sum / group.len()) as u32
+}
+
+/// Distinct-pattern counts under D4 symmetry, for every length allowed
+/// by `policy`.
+pub fn distinct_patterns_report<const N: usize>(policy: &PatternPolicy) -> Vec<(usize, u32)> {
+    (policy.min_len..=policy.max_len)
+        .map(|len| (len, distinct_patterns_of_length::<N>(len)))
+        .collect()
+}
+
+/// Equivalent bits of entropy of the keyspace allowed by `policy`, i.e.
+/// `log2(total patterns)`.
+pub fn entropy_bits<const N: usize>(policy: &PatternPolicy) -> f64 {
+    (count_patterns::<N>(policy) as f64).log2()
+}
+
+// Number of decimal digits a PIN would need to offer a comparable
+// guess-budget, i.e. the smallest `digits` such that `10^digits >= patterns`.
+fn equivalent_pin_digits(patterns: u64) -> u32 {
+    if patterns <= 1 {
+        return 0;
+    }
+    (patterns as f64).log10().ceil() as u32
+}
+
+/// A table, for every length from 1 to N*N points, of the pattern count
+/// at that length, the cumulative count up to and including it, and the
+/// marginal entropy (in bits) that patterns of that length add to the
+/// cumulative keyspace.
+pub fn report<const N: usize>() -> String {
+    let mut current = Step::<N>::init();
+    let mut cumulative: u64 = 0;
+    let mut previous_bits = 0.0;
+
+    let mut out = String::new();
+    out.push_str("length      count  cumulative    bits   Δbits\n");
+
+    for length in 1..=Position::<N>::num_points() {
+        if length > 1 {
+            current = current.next_step();
+            current.validate(length as u32);
+        }
+
+        let count = current.current_possibilities();
+        cumulative += count as u64;
+        let bits = (cumulative as f64).log2();
+        out.push_str(&format!(
+            "{length:>6}  {count:>9}  {cumulative:>10}  {bits:>6.2}  {:>6.2}\n",
+            bits - previous_bits
+        ));
+        previous_bits = bits;
+    }
+
+    let policy = PatternPolicy::android();
+    let android_total = count_patterns::<N>(&policy);
+    out.push_str(&format!(
+        "\n{}..={} point patterns \u{2248} {:.1} bits \u{2248} a {}-digit PIN\n",
+        policy.min_len,
+        policy.max_len,
+        (android_total as f64).log2(),
+        equivalent_pin_digits(android_total as u64)
+    ));
+
+    out
 }
 
 fn main() {
@@ -172,7 +529,11 @@ fn main() {
         println!( "{step} point swiped: {possibilities} possibilities");
     }
 
-    let mut current = Step::init();
+    // 3×3 Android-style keypad:
+    // 0 1 2
+    // 3 4 5
+    // 6 7 8
+    let mut current = Step::<3>::init();
 
     current.validate(1);
     display(1, current.current_possibilities());
@@ -189,15 +550,89 @@ fn main() {
 
     }
 
-    let mut sum_step_5_to_9 = 0;
-    for step in 5..=9 {
-        current = current.next_step();
+    let policy = PatternPolicy::android();
+    println!(
+        "total of possible combination ({}..={} points): {}",
+        policy.min_len,
+        policy.max_len,
+        count_patterns::<3>(&policy)
+    );
+
+    for (length, stuck) in stuck_prefixes_report::<3>(&policy) {
+        if stuck > 0 {
+            println!("{stuck} pattern(s) get stuck at {length} point(s), before reaching the required minimum");
+        }
+    }
 
-        current.validate(step);
-        display(step, current.current_possibilities());
+    println!();
+    println!("sample pattern per length (first found by DFS):");
+    for length in 1..=4usize {
+        if let Some(swipes) = patterns_of_length::<3>(length).next() {
+            let pattern: Pattern<3> = swipes.into();
+            println!("length {length}:\n{}", pattern.draw());
+        }
+    }
 
-        sum_step_5_to_9 += current.current_possibilities();
+    println!("distinct patterns under D4 symmetry:");
+    for (length, distinct) in distinct_patterns_report::<3>(&policy) {
+        println!("{length} point swiped: {distinct} distinct patterns");
     }
 
-    println!("total of possible combination: {sum_step_5_to_9}");
+    println!();
+    print!("{}", report::<3>());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_set_bit_ops() {
+        let empty = SquareSet::<3>::empty();
+        let a = empty.with(0).with(2);
+        let b = empty.with(2).with(4);
+
+        assert!(a.is_set(0) && a.is_set(2) && !a.is_set(4));
+        assert_eq!(a & b, empty.with(2));
+        assert_eq!(a | b, empty.with(0).with(2).with(4));
+        assert!((a & b).is_subset_of(a));
+        assert!(!a.is_subset_of(b));
+    }
+
+    #[test]
+    fn square_set_not_is_complement() {
+        let set = SquareSet::<3>::empty().with(0).with(4).with(8);
+        let complement = !set;
+        for point in 0..9 {
+            assert_eq!(complement.is_set(point), !set.is_set(point));
+        }
+    }
+
+    #[test]
+    fn patterns_of_length_matches_dp_counts() {
+        let mut step = Step::<3>::init();
+        for len in 1..=4u32 {
+            if len > 1 {
+                step = step.next_step();
+                step.validate(len);
+            }
+            let expected = step.current_possibilities();
+            let actual = patterns_of_length::<3>(len as usize).count() as u32;
+            assert_eq!(actual, expected, "length {len}");
+        }
+    }
+
+    #[test]
+    fn draw_renders_swipe_order() {
+        let pattern: Pattern<3> = vec![0, 4, 8].into();
+        assert_eq!(pattern.draw(), " 1  .  .\n .  2  .\n .  .  3\n");
+    }
+
+    #[test]
+    fn distinct_patterns_known_values() {
+        // every single point: 4 corners + 4 edges + 1 center = 3 orbits
+        assert_eq!(distinct_patterns_of_length::<3>(1), 3);
+        // corner-adjacent, corner-center, edge-center, … = 9 orbits
+        assert_eq!(distinct_patterns_of_length::<3>(2), 9);
+    }
 }